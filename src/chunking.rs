@@ -0,0 +1,120 @@
+use std::ops::Range;
+
+/// A slice of a source document along with the byte range it came from within that
+/// document, so retrieval results can point back to the specific passage instead of
+/// the whole file.
+#[derive(Debug, Clone)]
+pub struct Chunk {
+    pub text: String,
+    pub range: Range<usize>,
+}
+
+/// A retrieved passage together with where it came from.
+pub struct Passage {
+    pub text: String,
+    pub source: String,
+    pub range: Range<usize>,
+}
+
+// Rough tokens-per-character ratio for English text; good enough to stay under a
+// model's input budget without pulling in a real tokenizer.
+fn approx_tokens(text: &str) -> usize {
+    (text.len() / 4).max(1)
+}
+
+/// Splits `text` into chunks that each stay under `max_tokens`, preferring paragraph
+/// boundaries and falling back to sentence boundaries when a single paragraph is
+/// too large on its own.
+pub fn chunk_document(text: &str, max_tokens: usize) -> Vec<Chunk> {
+    let mut chunks = Vec::new();
+    let mut buffer = String::new();
+    let mut chunk_start = 0usize;
+    let mut pos = 0usize;
+
+    for paragraph in text.split_inclusive("\n\n") {
+        if approx_tokens(paragraph) > max_tokens {
+            for sentence in paragraph.split_inclusive(". ") {
+                if approx_tokens(sentence) > max_tokens {
+                    // Even a single sentence blows the budget on its own; flush
+                    // what we have, then hard-split the sentence itself so no
+                    // chunk can ever exceed max_tokens.
+                    flush_chunk(&mut buffer, &mut chunk_start, pos, &mut chunks);
+                    push_hard_split(sentence, pos, max_tokens, &mut chunks);
+                    pos += sentence.len();
+                    chunk_start = pos;
+                    continue;
+                }
+
+                if !buffer.is_empty() && approx_tokens(&buffer) + approx_tokens(sentence) > max_tokens {
+                    flush_chunk(&mut buffer, &mut chunk_start, pos, &mut chunks);
+                }
+                buffer.push_str(sentence);
+                pos += sentence.len();
+            }
+            continue;
+        }
+
+        if !buffer.is_empty() && approx_tokens(&buffer) + approx_tokens(paragraph) > max_tokens {
+            flush_chunk(&mut buffer, &mut chunk_start, pos, &mut chunks);
+        }
+
+        buffer.push_str(paragraph);
+        pos += paragraph.len();
+    }
+
+    flush_chunk(&mut buffer, &mut chunk_start, pos, &mut chunks);
+
+    chunks
+}
+
+// Pushes a chunk for the trimmed contents of `buffer`, with `range` tracking the
+// trimmed slice's actual position in the source document (not the untrimmed
+// buffer's), since `text` strips the leading/trailing whitespace `range` would
+// otherwise include.
+fn flush_chunk(buffer: &mut String, chunk_start: &mut usize, pos: usize, chunks: &mut Vec<Chunk>) {
+    if !buffer.is_empty() {
+        // An all-whitespace buffer trims down to nothing; pushing it anyway would
+        // emit an empty-text chunk with leading == trailing == buffer.len(), i.e. an
+        // inverted range (start > end).
+        if !buffer.trim().is_empty() {
+            let leading = buffer.len() - buffer.trim_start().len();
+            let trailing = buffer.len() - buffer.trim_end().len();
+
+            chunks.push(Chunk {
+                text: buffer.trim().to_string(),
+                range: (*chunk_start + leading)..(pos - trailing),
+            });
+        }
+        buffer.clear();
+    }
+    *chunk_start = pos;
+}
+
+// Hard-splits `sentence` (whose own token count already exceeds `max_tokens`) into
+// char-boundary-respecting pieces no larger than that budget, so a single unbroken
+// run of text can't produce an over-budget chunk.
+fn push_hard_split(sentence: &str, offset: usize, max_tokens: usize, chunks: &mut Vec<Chunk>) {
+    let max_bytes = (max_tokens * 4).max(1);
+    let mut start = 0;
+
+    while start < sentence.len() {
+        let mut end = (start + max_bytes).min(sentence.len());
+        while end < sentence.len() && !sentence.is_char_boundary(end) {
+            end += 1;
+        }
+
+        let piece = &sentence[start..end];
+        let leading = piece.len() - piece.trim_start().len();
+        let trailing = piece.len() - piece.trim_end().len();
+        let trimmed = piece.trim();
+
+        if !trimmed.is_empty() {
+            chunks.push(Chunk {
+                text: trimmed.to_string(),
+                range: (offset + start + leading)..(offset + end - trailing),
+            });
+        }
+
+        start = end;
+    }
+}
@@ -0,0 +1,167 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use futures::stream::{self, StreamExt};
+
+use async_openai::types::{CreateEmbeddingRequest, EmbeddingInput};
+use async_openai::{config::OpenAIConfig, Client, Embeddings};
+
+use crate::retry::{with_retry, RetryConfig};
+
+// How many chunks we'll embed against Ollama at once; OpenAI already batches a whole
+// array in one request so it doesn't need this.
+static DEFAULT_EMBED_CONCURRENCY: usize = 4;
+
+/// Abstracts over how text is turned into vectors so `RAGSystem` isn't hard-wired
+/// to a single embedding backend. Collections are sized from `dimensions()` so
+/// swapping providers can't silently produce mismatched collections.
+#[async_trait]
+pub trait EmbeddingProvider: Send + Sync {
+    async fn embed(&self, inputs: Vec<String>) -> Result<Vec<Vec<f32>>>;
+
+    fn dimensions(&self) -> u64;
+}
+
+pub struct OpenAIEmbeddingProvider {
+    client: Client<OpenAIConfig>,
+    model: String,
+    dimensions: u64,
+    retry_config: RetryConfig,
+}
+
+impl OpenAIEmbeddingProvider {
+    pub fn new(api_key: String) -> Self {
+        let openai_config = OpenAIConfig::new()
+            .with_api_key(api_key)
+            .with_org_id("qdrant-shuttle-semantic-cache");
+
+        Self {
+            client: Client::with_config(openai_config),
+            model: "text-embedding-ada-002".to_string(),
+            dimensions: 1536,
+            retry_config: RetryConfig::from_env(),
+        }
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for OpenAIEmbeddingProvider {
+    async fn embed(&self, inputs: Vec<String>) -> Result<Vec<Vec<f32>>> {
+        with_retry(self.retry_config, || async {
+            let embedding_request = CreateEmbeddingRequest {
+                model: self.model.clone(),
+                input: EmbeddingInput::StringArray(inputs.clone()),
+                encoding_format: None,
+                user: None,
+                dimensions: Some(self.dimensions as u32),
+            };
+
+            let embeddings = Embeddings::new(&self.client)
+                .create(embedding_request)
+                .await?;
+
+            if embeddings.data.is_empty() {
+                return Err(anyhow::anyhow!(
+                    "There were no embeddings returned by OpenAI"
+                ));
+            }
+
+            Ok(embeddings.data.into_iter().map(|x| x.embedding).collect())
+        })
+        .await
+    }
+
+    fn dimensions(&self) -> u64 {
+        self.dimensions
+    }
+}
+
+/// Talks to a locally hosted embedding model (e.g. Ollama) over its HTTP API so the
+/// same RAG pipeline can run against a local model instead of OpenAI.
+pub struct OllamaEmbeddingProvider {
+    base_url: String,
+    model: String,
+    dimensions: u64,
+    http_client: reqwest::Client,
+    retry_config: RetryConfig,
+    concurrency: usize,
+}
+
+impl OllamaEmbeddingProvider {
+    pub fn new(base_url: String, model: String, dimensions: u64) -> Self {
+        let concurrency = std::env::var("EMBED_CONCURRENCY")
+            .ok()
+            .and_then(|x| x.parse().ok())
+            .unwrap_or(DEFAULT_EMBED_CONCURRENCY);
+
+        Self {
+            base_url,
+            model,
+            dimensions,
+            http_client: reqwest::Client::new(),
+            retry_config: RetryConfig::from_env(),
+            concurrency,
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+struct OllamaEmbeddingRequest<'a> {
+    model: &'a str,
+    prompt: &'a str,
+}
+
+#[derive(serde::Deserialize)]
+struct OllamaEmbeddingResponse {
+    embedding: Vec<f32>,
+}
+
+#[async_trait]
+impl EmbeddingProvider for OllamaEmbeddingProvider {
+    async fn embed(&self, inputs: Vec<String>) -> Result<Vec<Vec<f32>>> {
+        // Embed chunks concurrently (bounded by `concurrency`) instead of one at a
+        // time, while still respecting the retry/backoff layer per request.
+        let results: Vec<Result<(usize, Vec<f32>)>> = stream::iter(inputs.into_iter().enumerate())
+            .map(|(index, input)| async move {
+                let embedding = with_retry(self.retry_config, || async {
+                    let response = self
+                        .http_client
+                        .post(format!("{}/api/embeddings", self.base_url))
+                        .json(&OllamaEmbeddingRequest {
+                            model: &self.model,
+                            prompt: &input,
+                        })
+                        .send()
+                        .await?
+                        .error_for_status()?
+                        .json::<OllamaEmbeddingResponse>()
+                        .await?;
+
+                    Ok(response.embedding)
+                })
+                .await?;
+
+                Ok((index, embedding))
+            })
+            .buffer_unordered(self.concurrency)
+            .collect()
+            .await;
+
+        let mut embeddings: Vec<Option<Vec<f32>>> = Vec::new();
+        for result in results {
+            let (index, embedding) = result?;
+            if index >= embeddings.len() {
+                embeddings.resize(index + 1, None);
+            }
+            embeddings[index] = Some(embedding);
+        }
+
+        Ok(embeddings
+            .into_iter()
+            .map(|e| e.expect("every index is populated exactly once"))
+            .collect())
+    }
+
+    fn dimensions(&self) -> u64 {
+        self.dimensions
+    }
+}
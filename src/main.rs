@@ -1,10 +1,15 @@
+mod chunking;
+mod embedding;
 mod qdrant;
+mod retry;
 
 use std::env;
 
 use axum::{Json, extract::State, response::IntoResponse, http::StatusCode};
 use axum::{routing::post, Router};
+use tokio::sync::mpsc;
 
+use embedding::{EmbeddingProvider, OllamaEmbeddingProvider, OpenAIEmbeddingProvider};
 use qdrant::RAGSystem;
 use qdrant_client::client::QdrantClient;
 
@@ -17,10 +22,23 @@ struct Prompt {
     prompt: String,
 }
 
+#[derive(Deserialize)]
+struct IngestRequest {
+    source: String,
+}
+
+#[derive(Clone)]
+struct AppState {
+    rag: RAGSystem,
+    ingest_tx: mpsc::Sender<IngestRequest>,
+}
+
 async fn prompt(
-    State(state): State<RAGSystem>,
+    State(state): State<AppState>,
     Json(prompt): Json<Prompt>,
 ) -> Result<impl IntoResponse, impl IntoResponse> {
+    let state = state.rag;
+
     let embedding = match state.embed_prompt(&prompt.prompt).await {
         Ok(embedding) => embedding,
         Err(e) => {
@@ -35,7 +53,8 @@ async fn prompt(
         return Ok(answer);
     }
 
-    let search_result = match state.search(embedding.clone()).await {
+    let with_keywords = state.hybrid_search_enabled();
+    let search_result = match state.search(embedding.clone(), &prompt.prompt, with_keywords).await {
         Ok(res) => res,
         Err(e) => {
             return Err((
@@ -45,7 +64,7 @@ async fn prompt(
         }
     };
 
-    let llm_response = match state.prompt(&prompt.prompt, &search_result).await {
+    let llm_response = match state.prompt(&prompt.prompt, &search_result.text).await {
         Ok(prompt_result) => prompt_result,
         Err(e) => {
             return Err((
@@ -65,6 +84,40 @@ async fn prompt(
     Ok(llm_response)
 }
 
+// Queues a source for re-ingestion instead of re-embedding it inline, so the request
+// returns immediately and a commit webhook (or similar) isn't left waiting on OpenAI.
+async fn ingest(
+    State(state): State<AppState>,
+    Json(request): Json<IngestRequest>,
+) -> impl IntoResponse {
+    if state.ingest_tx.send(request).await.is_err() {
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "The ingest queue is no longer accepting work".to_string(),
+        );
+    }
+
+    (StatusCode::ACCEPTED, "Queued for re-ingestion".to_string())
+}
+
+// Picks the embedding backend from the EMBEDDING_PROVIDER secret/env var, defaulting
+// to OpenAI. Set it to "ollama" to embed against a locally hosted model instead.
+fn build_embedding_provider() -> Box<dyn EmbeddingProvider> {
+    match env::var("EMBEDDING_PROVIDER").as_deref() {
+        Ok("ollama") => Box::new(OllamaEmbeddingProvider::new(
+            env::var("OLLAMA_BASE_URL").unwrap_or_else(|_| "http://localhost:11434".to_string()),
+            env::var("OLLAMA_EMBEDDING_MODEL").unwrap_or_else(|_| "nomic-embed-text".to_string()),
+            env::var("OLLAMA_EMBEDDING_DIMENSIONS")
+                .ok()
+                .and_then(|x| x.parse().ok())
+                .unwrap_or(768),
+        )),
+        _ => Box::new(OpenAIEmbeddingProvider::new(
+            env::var("OPENAI_API_KEY").unwrap(),
+        )),
+    }
+}
+
 #[shuttle_runtime::main]
 async fn main(
     #[shuttle_qdrant::Qdrant(
@@ -76,18 +129,53 @@ async fn main(
 ) -> shuttle_axum::ShuttleAxum {
     secrets.into_iter().for_each(|x| env::set_var(x.0, x.1));
 
-    let rag = RAGSystem::new(qdrant_client);
+    let embedding_provider = build_embedding_provider();
+
+    let rag = RAGSystem::new(qdrant_client, embedding_provider);
 
     let setup_required = true;
 
     if setup_required {
-        rag.create_cache_collection().await?;
+        rag.create_regular_collection().await?;
         rag.create_cache_collection().await?;
 
         rag.embed_and_upsert_csv_file("text.csv".into()).await?;
     }
 
-    let router = Router::new().route("/prompt", post(prompt)).with_state(rag);
+    let (ingest_tx, mut ingest_rx) = mpsc::channel::<IngestRequest>(32);
+
+    let ingest_rag = rag.clone();
+    tokio::spawn(async move {
+        while let Some(request) = ingest_rx.recv().await {
+            if let Err(e) = ingest_rag.reingest_source(&request.source).await {
+                println!("An error occurred while re-ingesting {}: {e}", request.source);
+            }
+        }
+    });
+
+    // Periodically reclaims expired/overflowing cache entries so `my-collection_cached`
+    // doesn't grow forever or keep serving answers generated against stale documents.
+    let eviction_rag = rag.clone();
+    let eviction_interval_secs = env::var("CACHE_EVICTION_INTERVAL_SECONDS")
+        .ok()
+        .and_then(|x| x.parse().ok())
+        .unwrap_or(3600);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(eviction_interval_secs));
+        loop {
+            interval.tick().await;
+            if let Err(e) = eviction_rag.evict_cache().await {
+                println!("An error occurred while evicting the cache: {e}");
+            }
+        }
+    });
+
+    let app_state = AppState { rag, ingest_tx };
+
+    let router = Router::new()
+        .route("/prompt", post(prompt))
+        .route("/ingest", post(ingest))
+        .with_state(app_state);
 
     Ok(router.into())
 }
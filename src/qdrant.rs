@@ -1,12 +1,29 @@
 use anyhow::Result;
+use std::collections::HashSet;
 use std::env;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use uuid::Uuid;
 
 use qdrant_client::prelude::{CreateCollection, Distance, PointStruct, QdrantClient};
 use qdrant_client::qdrant::{
+    point_id::PointIdOptions,
+    points_selector::PointsSelectorOneOf,
     vectors_config::Config,
     with_payload_selector::SelectorOptions,
+    CountPoints,
+    Condition,
+    Direction,
+    Filter,
+    FieldType,
+    OrderBy,
+    PointId,
+    PointsIdsList,
+    PointsSelector,
+    Range,
+    ScrollPoints,
     VectorParams,
     VectorsConfig,
     WithPayloadSelector,
@@ -17,24 +34,166 @@ use async_openai::types::{
     ChatCompletionRequestSystemMessageArgs,
     ChatCompletionRequestUserMessageArgs,
     CreateChatCompletionRequestArgs,
-    CreateEmbeddingRequest,
-    EmbeddingInput
 };
 
-use async_openai::{config::OpenAIConfig, Client, Embeddings};
+use async_openai::{config::OpenAIConfig, Client};
+
+use crate::chunking::{chunk_document, Chunk, Passage};
+use crate::embedding::EmbeddingProvider;
+use crate::retry::{with_retry, RetryConfig};
 
 #[derive(Clone)]
 pub struct RAGSystem {
     qdrant_client: Arc<QdrantClient>,
-    openai_client: Client<OpenAIConfig>
+    openai_client: Client<OpenAIConfig>,
+    embedding_provider: Arc<dyn EmbeddingProvider>,
+    cache_score_threshold: f32,
+    chunk_max_tokens: usize,
+    retry_config: RetryConfig,
+    hybrid_search_enabled: bool,
+    cache_ttl_seconds: u64,
+    cache_max_size: u64,
 }
 
 static REGULAR_COLLECTION_NAME: &str = "my-collection";
 static CACHE_COLLECTION_NAME: &str = "my-collection_cached";
 
+// Minimum cosine similarity a cache point must score to be treated as a hit.
+// Configurable via the CACHE_SCORE_THRESHOLD secret/env var.
+static DEFAULT_CACHE_SCORE_THRESHOLD: f32 = 0.95;
+
+// Upper bound (in approximate tokens) for a single ingested chunk.
+// Configurable via the CHUNK_MAX_TOKENS secret/env var.
+static DEFAULT_CHUNK_MAX_TOKENS: usize = 500;
+
+// How long a cache entry stays valid before `evict_cache` reclaims it.
+// Configurable via the CACHE_TTL_SECONDS secret/env var.
+static DEFAULT_CACHE_TTL_SECONDS: u64 = 60 * 60 * 24 * 7;
+
+// Cache size cap; once exceeded, the least-recently-hit entries are evicted first.
+// Configurable via the CACHE_MAX_SIZE secret/env var.
+static DEFAULT_CACHE_MAX_SIZE: u64 = 10_000;
+
+// Directory that `reingest_source` confines ingestable files to, so POST /ingest
+// can't be used to read arbitrary files off the host. Configurable via INGEST_DIR.
+static DEFAULT_INGEST_DIR: &str = ".";
+
+// Reciprocal-rank-fusion constant; higher values flatten the influence of rank.
+static RRF_K: f32 = 60.0;
+
+// How many candidates each of the dense/keyword searches contributes to fusion.
+static HYBRID_CANDIDATE_LIMIT: u64 = 10;
+
+// Qdrant's text-match filter only tells us which points contain the term, not how
+// well they match, so keyword_search pulls this many times `limit` candidates and
+// ranks them itself before truncating.
+static KEYWORD_CANDIDATE_POOL_FACTOR: u64 = 5;
+
+// Page size for delete_stale_chunks's scroll loop; kept well above the default
+// chunk count for a single source while still paginating via next_page_offset for
+// sources large enough to exceed it.
+static DELETE_STALE_SCROLL_PAGE_SIZE: u32 = 1000;
+
+/// A single candidate from either the dense or keyword search, tagged with its
+/// point id so results from both lists can be fused by identity.
+struct RankedPassage {
+    id: String,
+    passage: Passage,
+}
+
+/// Derives a stable point id from a chunk's source and byte range, so re-ingesting a
+/// source updates its existing points instead of minting fresh ones every time.
+fn chunk_point_id(source: &str, range: &std::ops::Range<usize>) -> String {
+    let name = format!("{source}#{}-{}", range.start, range.end);
+    Uuid::new_v5(&Uuid::NAMESPACE_URL, name.as_bytes()).to_string()
+}
+
+// Resolves `source` against INGEST_DIR and rejects it if it escapes that directory
+// (via an absolute path or `..` traversal), so the unauthenticated /ingest endpoint
+// can't be used to pull arbitrary files off the host into the vector store.
+fn resolve_ingest_path(source: &str) -> Result<PathBuf> {
+    let root = env::var("INGEST_DIR").unwrap_or_else(|_| DEFAULT_INGEST_DIR.to_string());
+    let root = PathBuf::from(root)
+        .canonicalize()
+        .map_err(|e| anyhow::anyhow!("INGEST_DIR is not a valid directory: {e}"))?;
+
+    let candidate = root
+        .join(source)
+        .canonicalize()
+        .map_err(|e| anyhow::anyhow!("Could not resolve ingest source {source}: {e}"))?;
+
+    if !candidate.starts_with(&root) {
+        return Err(anyhow::anyhow!(
+            "Refusing to ingest {source}: it resolves outside of INGEST_DIR"
+        ));
+    }
+
+    Ok(candidate)
+}
+
+fn point_id_to_string(id: &Option<PointId>) -> Option<String> {
+    match id.as_ref()?.point_id_options.as_ref()? {
+        PointIdOptions::Uuid(s) => Some(s.clone()),
+        PointIdOptions::Num(n) => Some(n.to_string()),
+    }
+}
+
+fn payload_to_passage(payload: &std::collections::HashMap<String, qdrant_client::qdrant::Value>) -> Passage {
+    let text = payload.get("document").map(|x| x.to_string()).unwrap_or_default();
+    let source = payload.get("source").map(|x| x.to_string()).unwrap_or_default();
+    let start = payload
+        .get("start")
+        .and_then(|x| x.to_string().parse::<usize>().ok())
+        .unwrap_or(0);
+    let end = payload
+        .get("end")
+        .and_then(|x| x.to_string().parse::<usize>().ok())
+        .unwrap_or(0);
+
+    Passage {
+        text,
+        source,
+        range: start..end,
+    }
+}
+
+/// Fuses multiple ranked candidate lists (e.g. dense + keyword) with Reciprocal Rank
+/// Fusion: `score = sum(1 / (RRF_K + rank))` across every list a passage appears in,
+/// then sorts descending by that score.
+fn fuse_with_rrf(lists: Vec<Vec<RankedPassage>>) -> Vec<Passage> {
+    use std::collections::HashMap;
+
+    let mut scores: HashMap<String, f32> = HashMap::new();
+    let mut passages: HashMap<String, Passage> = HashMap::new();
+
+    for list in lists {
+        for (rank, ranked) in list.into_iter().enumerate() {
+            let score = 1.0 / (RRF_K + (rank + 1) as f32);
+            *scores.entry(ranked.id.clone()).or_insert(0.0) += score;
+            passages.entry(ranked.id).or_insert(ranked.passage);
+        }
+    }
+
+    let mut ranked_ids: Vec<(String, f32)> = scores.into_iter().collect();
+    ranked_ids.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+
+    ranked_ids
+        .into_iter()
+        .filter_map(|(id, _)| passages.remove(&id))
+        .collect()
+}
+
+// Counts how many distinct query terms appear in `text`; a crude relevance proxy
+// standing in for real BM25 scoring so the keyword list is at least ordered by
+// match quality instead of arbitrary scroll (point-id) order.
+fn term_overlap_score(query_terms: &HashSet<String>, text: &str) -> usize {
+    let text_terms: HashSet<String> = text.split_whitespace().map(|term| term.to_lowercase()).collect();
+    query_terms.intersection(&text_terms).count()
+}
+
 impl RAGSystem {
     // Initialising our regular collection
-    pub fn new(qdrant_client: QdrantClient) -> Self {
+    pub fn new(qdrant_client: QdrantClient, embedding_provider: Box<dyn EmbeddingProvider>) -> Self {
         let openai_api_key = env::var("OPENAI_API_KEY").unwrap();
 
         let openai_config = OpenAIConfig::new()
@@ -43,12 +202,49 @@ impl RAGSystem {
 
         let openai_client = Client::with_config(openai_config);
 
+        let cache_score_threshold = env::var("CACHE_SCORE_THRESHOLD")
+            .ok()
+            .and_then(|x| x.parse::<f32>().ok())
+            .unwrap_or(DEFAULT_CACHE_SCORE_THRESHOLD);
+
+        let chunk_max_tokens = env::var("CHUNK_MAX_TOKENS")
+            .ok()
+            .and_then(|x| x.parse::<usize>().ok())
+            .unwrap_or(DEFAULT_CHUNK_MAX_TOKENS);
+
+        let hybrid_search_enabled = env::var("HYBRID_SEARCH_ENABLED")
+            .ok()
+            .and_then(|x| x.parse::<bool>().ok())
+            .unwrap_or(false);
+
+        let cache_ttl_seconds = env::var("CACHE_TTL_SECONDS")
+            .ok()
+            .and_then(|x| x.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_CACHE_TTL_SECONDS);
+
+        let cache_max_size = env::var("CACHE_MAX_SIZE")
+            .ok()
+            .and_then(|x| x.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_CACHE_MAX_SIZE);
+
         Self {
             openai_client,
             qdrant_client: Arc::new(qdrant_client),
+            embedding_provider: Arc::from(embedding_provider),
+            cache_score_threshold,
+            chunk_max_tokens,
+            retry_config: RetryConfig::from_env(),
+            hybrid_search_enabled,
+            cache_ttl_seconds,
+            cache_max_size,
         }
     }
 
+    /// Whether `search` should also run a keyword pass and fuse it in with RRF.
+    pub fn hybrid_search_enabled(&self) -> bool {
+        self.hybrid_search_enabled
+    }
+
     /* Creating collection */
     pub async fn create_regular_collection(&self) -> Result<()> {
         self.qdrant_client
@@ -56,7 +252,7 @@ impl RAGSystem {
                 collection_name: REGULAR_COLLECTION_NAME.to_string(),
                 vectors_config: Some(VectorsConfig {
                     config: Some(Config::Params(VectorParams {
-                        size: 1536,
+                        size: self.embedding_provider.dimensions(),
                         distance: Distance::Cosine.into(),
                         hnsw_config: None,
                         quantization_config: None,
@@ -67,6 +263,17 @@ impl RAGSystem {
             })
             .await?;
 
+        // Needed for the keyword side of hybrid search in `search`
+        self.qdrant_client
+            .create_field_index(
+                REGULAR_COLLECTION_NAME,
+                "document",
+                FieldType::Text,
+                None,
+                None,
+            )
+            .await?;
+
         Ok(())
     }
 
@@ -77,8 +284,9 @@ impl RAGSystem {
                 collection_name: CACHE_COLLECTION_NAME.to_string(),
                 vectors_config: Some(VectorsConfig {
                     config: Some(Config::Params(VectorParams {
-                        size: 1536,
-                        distance: Distance::Euclid.into(),
+                        size: self.embedding_provider.dimensions(),
+                        // Cosine so a single "minimum similarity" threshold governs cache hits
+                        distance: Distance::Cosine.into(),
                         hnsw_config: None,
                         quantization_config: None,
                         on_disk: None,
@@ -89,6 +297,17 @@ impl RAGSystem {
             })
             .await?;
 
+        // Needed for `order_by` in `evict_cache_entries_over_capacity`
+        self.qdrant_client
+            .create_field_index(
+                CACHE_COLLECTION_NAME,
+                "last_hit",
+                FieldType::Integer,
+                None,
+                None,
+            )
+            .await?;
+
         Ok(())
     }
 
@@ -96,80 +315,158 @@ impl RAGSystem {
     pub async fn embed_and_upsert_csv_file(&self, file_path: PathBuf) -> Result<()> {
         let file_contents = std::fs::read_to_string(&file_path)?;
 
-        // note here that we skip 1 because CSV files typically have headers
-        // if you don't have any headers, you can remove it
-        let chunked_file_contents: Vec<String> = file_contents
-            .lines()
-            .skip(1)
-            .map(|x| x.to_owned())
-            .collect();
+        // note here that we skip past the header line because CSV files typically have one
+        // if you don't have any headers, you can remove this
+        let header_len = file_contents
+            .find('\n')
+            .map(|i| i + 1)
+            .unwrap_or(file_contents.len());
+        let body = &file_contents[header_len..];
 
-        let embedding_request = CreateEmbeddingRequest {
-            model: "text-embedding-ada-002".to_string(),
-            input: EmbeddingInput::StringArray(chunked_file_contents.to_owned()),
-            encoding_format: None,
-            user: None,
-            dimensions: Some(1536),
-        };
+        let source = file_path.to_string_lossy().into_owned();
+        let chunks = chunk_document(body, self.chunk_max_tokens);
 
-        let embeddings = Embeddings::new(&self.openai_client)
-            .create(embedding_request)
-            .await?;
+        if chunks.is_empty() {
+            return Ok(());
+        }
 
-        if embeddings.data.is_empty() {
-            return Err(anyhow::anyhow!(
-                "There were no embeddings returned by OpenAI"
-            ));
+        let chunk_texts: Vec<String> = chunks.iter().map(|c| c.text.clone()).collect();
+        let embeddings_vec = self.embedding_provider.embed(chunk_texts).await?;
+
+        for (chunk, embedding) in chunks.into_iter().zip(embeddings_vec) {
+            let id = chunk_point_id(&source, &chunk.range);
+            self.upsert_chunk(id, embedding, &source, header_len, &chunk)
+                .await?;
         }
 
-        let embeddings_vec: Vec<Vec<f32>> =
-            embeddings.data.into_iter().map(|x| x.embedding).collect();
+        Ok(())
+    }
+
+    /* Re-embeds `source` from disk and upserts its chunks using ids derived from
+    (source, chunk range), so re-ingesting updates existing points instead of
+    duplicating them, then deletes any points for this source whose chunk no longer
+    exists */
+    pub async fn reingest_source(&self, source: &str) -> Result<()> {
+        let path = resolve_ingest_path(source)?;
+        let file_contents = std::fs::read_to_string(&path)?;
+
+        let header_len = file_contents
+            .find('\n')
+            .map(|i| i + 1)
+            .unwrap_or(file_contents.len());
+        let body = &file_contents[header_len..];
+
+        let chunks = chunk_document(body, self.chunk_max_tokens);
+
+        let chunk_texts: Vec<String> = chunks.iter().map(|c| c.text.clone()).collect();
+        let embeddings_vec = if chunk_texts.is_empty() {
+            Vec::new()
+        } else {
+            self.embedding_provider.embed(chunk_texts).await?
+        };
 
-        // note that we create the upsert_embedding function later on
-        for embedding in embeddings_vec {
-            self.upset_embedding(embedding, file_contents.clone())
+        let mut current_ids = HashSet::with_capacity(chunks.len());
+
+        for (chunk, embedding) in chunks.into_iter().zip(embeddings_vec) {
+            let id = chunk_point_id(source, &chunk.range);
+            current_ids.insert(id.clone());
+            self.upsert_chunk(id, embedding, source, header_len, &chunk)
                 .await?;
         }
 
+        self.delete_stale_chunks(source, &current_ids).await?;
+
         Ok(())
     }
 
-    /* need to embed any further inputs to search for any matching embeddings */
-    pub async fn embed_prompt(&self, prompt: &str) -> Result<Vec<f32>> {
-        let embedding_request = CreateEmbeddingRequest {
-            model: "text-embedding-ada-002".to_string(),
-            input: EmbeddingInput::String(prompt.to_owned()),
-            encoding_format: None,
-            user: None,
-            dimensions: Some(1536),
+    /* Deletes points tagged with `source` whose id isn't in `current_ids`, i.e. chunks
+    that existed on a previous ingest but no longer do */
+    async fn delete_stale_chunks(&self, source: &str, current_ids: &HashSet<String>) -> Result<()> {
+        let mut stale_ids: Vec<PointId> = Vec::new();
+        let mut offset = None;
+
+        // A source can chunk into far more than Qdrant's default scroll page (10
+        // points), so keep following `next_page_offset` until it comes back empty
+        // instead of only inspecting the first page.
+        loop {
+            let scroll_points = ScrollPoints {
+                collection_name: REGULAR_COLLECTION_NAME.to_string(),
+                filter: Some(Filter {
+                    must: vec![Condition::matches("source", source.to_string())],
+                    ..Default::default()
+                }),
+                limit: Some(DELETE_STALE_SCROLL_PAGE_SIZE),
+                offset,
+                with_payload: Some(WithPayloadSelector {
+                    selector_options: Some(SelectorOptions::Enable(false)),
+                }),
+                ..Default::default()
+            };
+
+            let scroll_result = self.qdrant_client.scroll(&scroll_points).await?;
+
+            stale_ids.extend(
+                scroll_result
+                    .result
+                    .into_iter()
+                    .filter_map(|point| point_id_to_string(&point.id))
+                    .filter(|id| !current_ids.contains(id))
+                    .map(PointId::from),
+            );
+
+            offset = scroll_result.next_page_offset;
+            if offset.is_none() {
+                break;
+            }
+        }
+
+        if stale_ids.is_empty() {
+            return Ok(());
+        }
+
+        let points_selector = PointsSelector {
+            points_selector_one_of: Some(PointsSelectorOneOf::Points(PointsIdsList {
+                ids: stale_ids,
+            })),
         };
 
-        let embeddings = Embeddings::new(&self.openai_client)
-            .create(embedding_request)
+        self.qdrant_client
+            .delete_points(REGULAR_COLLECTION_NAME, None, &points_selector, None)
             .await?;
 
-        if embeddings.data.is_empty() {
-            return Err(anyhow::anyhow!(
-                "There were no embeddings returned by OpenAI!"
-            ));
-        }
+        Ok(())
+    }
 
-        Ok(embeddings.data.into_iter().next().unwrap().embedding)
+    /* need to embed any further inputs to search for any matching embeddings */
+    pub async fn embed_prompt(&self, prompt: &str) -> Result<Vec<f32>> {
+        let embeddings = self.embedding_provider.embed(vec![prompt.to_owned()]).await?;
+
+        embeddings
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("There were no embeddings returned by the embedding provider!"))
     }
 
-    /* Upserting embeddings */
-    async fn upset_embedding(&self, embedding: Vec<f32>, file_contents: String) -> Result<()> {
-        let playload = serde_json::json!({
-            "document": file_contents
+    /* Upserting a single chunk's embedding under a deterministic id, tagged with
+    where it came from */
+    async fn upsert_chunk(
+        &self,
+        id: String,
+        embedding: Vec<f32>,
+        source: &str,
+        offset: usize,
+        chunk: &Chunk,
+    ) -> Result<()> {
+        let payload = serde_json::json!({
+            "document": chunk.text,
+            "source": source,
+            "start": chunk.range.start + offset,
+            "end": chunk.range.end + offset,
         })
             .try_into()
             .map_err(|x| anyhow::anyhow!("Ran into an error when converting the payload: {x}"))?;
 
-        let points = vec![PointStruct::new(
-            uuid::Uuid::new_v4().to_string(),
-            embedding,
-            playload,
-        )];
+        let points = vec![PointStruct::new(id, embedding, payload)];
 
         self.qdrant_client
             .upsert_points(REGULAR_COLLECTION_NAME.to_owned(), None, points, None)
@@ -178,10 +475,19 @@ impl RAGSystem {
         Ok(())
     }
 
-    /* Adding things to our cache */
+    /* Adding things to our cache, tagged with when they were cached, how often
+    they've been hit, and when they were last hit so `evict_cache` can enforce TTL
+    and max-size */
     pub async fn add_to_cache(&self, embedding: Vec<f32>, answer: &str) -> Result<()> {
+        let created_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+
         let payload = serde_json::json!({
-            "answer": answer
+            "answer": answer,
+            "created_at": created_at,
+            "hits": 0,
+            // A fresh entry counts as just hit, so it isn't evicted ahead of genuinely
+            // stale ones before it's ever had a chance to be looked up
+            "last_hit": created_at,
         })
             .try_into()
             .map_err(|x| anyhow::anyhow!("Ran into an error when converting the playload: {x}"))?;
@@ -199,8 +505,28 @@ impl RAGSystem {
         Ok(())
     }
 
-    /* Searching Qdrant collections */
-    pub async fn search(&self, embedding: Vec<f32>) -> Result<String> {
+    /* Searching Qdrant collections, optionally fusing in a keyword pass */
+    pub async fn search(&self, embedding: Vec<f32>, query: &str, with_keywords: bool) -> Result<Passage> {
+        if !with_keywords {
+            let dense = self.dense_search(embedding, 1).await?;
+            let Some(top) = dense.into_iter().next() else {
+                return Err(anyhow::anyhow!("There's nothing matching."));
+            };
+            return Ok(top.passage);
+        }
+
+        let dense = self.dense_search(embedding, HYBRID_CANDIDATE_LIMIT).await?;
+        let keyword = self.keyword_search(query, HYBRID_CANDIDATE_LIMIT).await?;
+
+        let Some(top) = fuse_with_rrf(vec![dense, keyword]).into_iter().next() else {
+            return Err(anyhow::anyhow!("There's nothing matching."));
+        };
+
+        Ok(top)
+    }
+
+    /* Pure vector similarity search against the regular collection */
+    async fn dense_search(&self, embedding: Vec<f32>, limit: u64) -> Result<Vec<RankedPassage>> {
         let payload_selector = WithPayloadSelector {
             selector_options: Some(SelectorOptions::Enable(true)),
         };
@@ -208,8 +534,10 @@ impl RAGSystem {
         let search_points = SearchPoints {
             collection_name: REGULAR_COLLECTION_NAME.to_string(),
             vector: embedding,
-            limit: 1,
+            limit,
             with_payload: Some(payload_selector),
+            // No gating here: we always want the closest passages as context for the LLM
+            score_threshold: None,
             ..Default::default()
         };
 
@@ -219,13 +547,67 @@ impl RAGSystem {
             .await
             .inspect_err(|x| println!("An error occurred while searching for points: {x}"))?;
 
-        let result = search_result.result.into_iter().next();
+        Ok(search_result
+            .result
+            .into_iter()
+            .filter_map(|result| {
+                let id = point_id_to_string(&result.id)?;
+                Some(RankedPassage {
+                    id,
+                    passage: payload_to_passage(&result.payload),
+                })
+            })
+            .collect())
+    }
 
-        let Some(result) = result else {
-            return Err(anyhow::anyhow!("There's nothing matching."))
+    /* Exact-term search over the stored `document` payload, for queries (error codes,
+    identifiers, proper nouns) that embeddings tend to blur together. Qdrant's filter
+    only tells us which points contain the term, not how well they match, so we pull
+    a wider candidate pool and rank it ourselves by term overlap before truncating to
+    `limit` -- otherwise RRF would fuse in scroll (point-id) order, which carries no
+    relevance signal at all */
+    async fn keyword_search(&self, query: &str, limit: u64) -> Result<Vec<RankedPassage>> {
+        let payload_selector = WithPayloadSelector {
+            selector_options: Some(SelectorOptions::Enable(true)),
         };
 
-        Ok(result.payload.get("document").unwrap().to_string())
+        let scroll_points = ScrollPoints {
+            collection_name: REGULAR_COLLECTION_NAME.to_string(),
+            filter: Some(Filter {
+                must: vec![Condition::matches_text("document", query.to_string())],
+                ..Default::default()
+            }),
+            limit: Some((limit * KEYWORD_CANDIDATE_POOL_FACTOR) as u32),
+            with_payload: Some(payload_selector),
+            ..Default::default()
+        };
+
+        let scroll_result = self
+            .qdrant_client
+            .scroll(&scroll_points)
+            .await
+            .inspect_err(|x| println!("An error occurred while keyword-searching for points: {x}"))?;
+
+        let query_terms: HashSet<String> = query
+            .split_whitespace()
+            .map(|term| term.to_lowercase())
+            .collect();
+
+        let mut candidates: Vec<(usize, RankedPassage)> = scroll_result
+            .result
+            .into_iter()
+            .filter_map(|result| {
+                let id = point_id_to_string(&result.id)?;
+                let passage = payload_to_passage(&result.payload);
+                let score = term_overlap_score(&query_terms, &passage.text);
+                Some((score, RankedPassage { id, passage }))
+            })
+            .collect();
+
+        candidates.sort_by(|a, b| b.0.cmp(&a.0));
+        candidates.truncate(limit as usize);
+
+        Ok(candidates.into_iter().map(|(_, ranked)| ranked).collect())
     }
 
     /* searching your cache collection */
@@ -239,6 +621,8 @@ impl RAGSystem {
             vector: embedding,
             limit: 1,
             with_payload: Some(payload_selector),
+            // Only points at least this similar count as a cache hit
+            score_threshold: Some(self.cache_score_threshold),
             ..Default::default()
         };
 
@@ -251,10 +635,149 @@ impl RAGSystem {
         let result = search_result.result.into_iter().next();
 
         let Some(result) = result else {
-            return Err(anyhow::anyhow!("There's nothing matching."))
+            return Err(anyhow::anyhow!("There's nothing matching the cache threshold."))
+        };
+
+        let answer = result.payload.get("answer").unwrap().to_string();
+
+        // Spawned rather than awaited: bumping the hit counter is best-effort
+        // bookkeeping for eviction and shouldn't add a Qdrant round-trip to the
+        // cache-hit response latency.
+        let rag = self.clone();
+        let id = result.id.clone();
+        let payload = result.payload.clone();
+        tokio::spawn(async move { rag.bump_cache_hits(&id, &payload).await });
+
+        Ok(answer)
+    }
+
+    async fn bump_cache_hits(&self, id: &Option<PointId>, payload: &std::collections::HashMap<String, qdrant_client::qdrant::Value>) {
+        let Some(id) = point_id_to_string(id) else {
+            return;
         };
 
-        Ok(result.payload.get("answer").unwrap().to_string())
+        let hits = payload
+            .get("hits")
+            .and_then(|x| x.to_string().parse::<u64>().ok())
+            .unwrap_or(0);
+
+        let Ok(last_hit) = SystemTime::now().duration_since(UNIX_EPOCH) else {
+            return;
+        };
+
+        let Ok(payload) = serde_json::json!({ "hits": hits + 1, "last_hit": last_hit.as_secs() }).try_into() else {
+            return;
+        };
+
+        let points_selector = PointsSelector {
+            points_selector_one_of: Some(PointsSelectorOneOf::Points(PointsIdsList {
+                ids: vec![PointId::from(id)],
+            })),
+        };
+
+        if let Err(e) = self
+            .qdrant_client
+            .set_payload(CACHE_COLLECTION_NAME, None, &points_selector, &payload, None, None)
+            .await
+        {
+            println!("An error occurred while bumping cache hits: {e}");
+        }
+    }
+
+    /* Evicts cache entries older than `cache_ttl_seconds`, then evicts the
+    least-recently-hit entries over `cache_max_size` */
+    pub async fn evict_cache(&self) -> Result<()> {
+        self.evict_expired_cache_entries().await?;
+        self.evict_cache_entries_over_capacity().await?;
+
+        Ok(())
+    }
+
+    async fn evict_expired_cache_entries(&self) -> Result<()> {
+        let cutoff = SystemTime::now()
+            .duration_since(UNIX_EPOCH)?
+            .as_secs()
+            .saturating_sub(self.cache_ttl_seconds);
+
+        let points_selector = PointsSelector {
+            points_selector_one_of: Some(PointsSelectorOneOf::Filter(Filter {
+                must: vec![Condition::range(
+                    "created_at",
+                    Range {
+                        lt: Some(cutoff as f64),
+                        ..Default::default()
+                    },
+                )],
+                ..Default::default()
+            })),
+        };
+
+        self.qdrant_client
+            .delete_points(CACHE_COLLECTION_NAME, None, &points_selector, None)
+            .await?;
+
+        Ok(())
+    }
+
+    // Evicts the least-recently-hit entries over `cache_max_size`, ordering by
+    // `last_hit` rather than the raw `hits` count -- a brand-new, never-hit entry
+    // still has a fresh `last_hit` (set at insert time), so it isn't evicted ahead
+    // of an entry that was hit frequently but a long time ago.
+    async fn evict_cache_entries_over_capacity(&self) -> Result<()> {
+        let count_result = self
+            .qdrant_client
+            .count(&CountPoints {
+                collection_name: CACHE_COLLECTION_NAME.to_string(),
+                ..Default::default()
+            })
+            .await?;
+
+        let count = count_result.result.map(|x| x.count).unwrap_or(0);
+
+        if count <= self.cache_max_size {
+            return Ok(());
+        }
+
+        let overflow = count - self.cache_max_size;
+
+        let scroll_points = ScrollPoints {
+            collection_name: CACHE_COLLECTION_NAME.to_string(),
+            limit: Some(overflow as u32),
+            order_by: Some(OrderBy {
+                key: "last_hit".to_string(),
+                direction: Some(Direction::Asc.into()),
+                start_from: None,
+            }),
+            with_payload: Some(WithPayloadSelector {
+                selector_options: Some(SelectorOptions::Enable(false)),
+            }),
+            ..Default::default()
+        };
+
+        let scroll_result = self.qdrant_client.scroll(&scroll_points).await?;
+
+        let stale_ids: Vec<PointId> = scroll_result
+            .result
+            .into_iter()
+            .filter_map(|point| point_id_to_string(&point.id))
+            .map(PointId::from)
+            .collect();
+
+        if stale_ids.is_empty() {
+            return Ok(());
+        }
+
+        let points_selector = PointsSelector {
+            points_selector_one_of: Some(PointsSelectorOneOf::Points(PointsIdsList {
+                ids: stale_ids,
+            })),
+        };
+
+        self.qdrant_client
+            .delete_points(CACHE_COLLECTION_NAME, None, &points_selector, None)
+            .await?;
+
+        Ok(())
     }
 
     /* Prompting */
@@ -267,34 +790,38 @@ impl RAGSystem {
             "
         );
 
-        let res = self.openai_client
-            .chat()
-            .create(
-                CreateChatCompletionRequestArgs::default()
-                    .model("gpt-4o")
-                    .messages(vec![
-                        // First we add the system message to define what the Agent does
-                        ChatCompletionRequestMessage::System(
-                            ChatCompletionRequestSystemMessageArgs::default()
-                                .build()?,
-                        ),
-                        // Then we add our prompt
-                        ChatCompletionRequestMessage::User(
-                            ChatCompletionRequestUserMessageArgs::default()
-                                .content(input)
-                                .build()?,
-                        ),
-                    ])
-                    .build()?,
-            )
-            .await
-            .map(|res| {
-                // We extract the first one
-                match res.choices[0].message.content.clone() {
-                    Some(res) => Ok(res),
-                    None => Err(anyhow::anyhow!("There was no result from OpenAI")),
-                }
-            })??;
+        let res = with_retry(self.retry_config, || async {
+            self.openai_client
+                .chat()
+                .create(
+                    CreateChatCompletionRequestArgs::default()
+                        .model("gpt-4o")
+                        .messages(vec![
+                            // First we add the system message to define what the Agent does
+                            ChatCompletionRequestMessage::System(
+                                ChatCompletionRequestSystemMessageArgs::default()
+                                    .build()?,
+                            ),
+                            // Then we add our prompt
+                            ChatCompletionRequestMessage::User(
+                                ChatCompletionRequestUserMessageArgs::default()
+                                    .content(input.clone())
+                                    .build()?,
+                            ),
+                        ])
+                        .build()?,
+                )
+                .await
+                .map_err(anyhow::Error::from)
+        })
+        .await
+        .and_then(|res| {
+            // We extract the first one
+            match res.choices[0].message.content.clone() {
+                Some(res) => Ok(res),
+                None => Err(anyhow::anyhow!("There was no result from OpenAI")),
+            }
+        })?;
 
         println!("Retrieved result from prompt: {res}");
 
@@ -0,0 +1,111 @@
+use std::future::Future;
+use std::time::Duration;
+
+use anyhow::Result;
+
+enum RetryClass {
+    /// Not worth retrying, e.g. a 4xx other than 429.
+    GiveUp,
+    /// Transient (5xx/network) failure - retry with exponential backoff.
+    RetryLater,
+    /// Rate-limited (429) - retry with a gentler, additive backoff.
+    RateLimited,
+}
+
+/// Retry tuning shared by every OpenAI/HTTP call site.
+#[derive(Clone, Copy)]
+pub struct RetryConfig {
+    pub max_attempts: u32,
+    pub base_delay_ms: u64,
+}
+
+impl RetryConfig {
+    /// Reads `RETRY_MAX_ATTEMPTS` / `RETRY_BASE_DELAY_MS`, falling back to sane
+    /// defaults when unset.
+    pub fn from_env() -> Self {
+        Self {
+            max_attempts: std::env::var("RETRY_MAX_ATTEMPTS")
+                .ok()
+                .and_then(|x| x.parse().ok())
+                .unwrap_or(5),
+            base_delay_ms: std::env::var("RETRY_BASE_DELAY_MS")
+                .ok()
+                .and_then(|x| x.parse().ok())
+                .unwrap_or(1),
+        }
+    }
+}
+
+fn classify(err: &anyhow::Error) -> RetryClass {
+    if let Some(reqwest_err) = err.downcast_ref::<reqwest::Error>() {
+        return classify_reqwest(reqwest_err);
+    }
+
+    if let Some(openai_err) = err.downcast_ref::<async_openai::error::OpenAIError>() {
+        return classify_openai(openai_err);
+    }
+
+    RetryClass::GiveUp
+}
+
+fn classify_reqwest(err: &reqwest::Error) -> RetryClass {
+    match err.status().map(|s| s.as_u16()) {
+        Some(429) => RetryClass::RateLimited,
+        Some(500..=599) => RetryClass::RetryLater,
+        Some(_) => RetryClass::GiveUp,
+        // No status at all means the request never got a response (connect/timeout/etc.)
+        None => RetryClass::RetryLater,
+    }
+}
+
+// async-openai wraps every HTTP call in its own error type rather than surfacing a
+// bare reqwest::Error, so OpenAI call sites need to classify this instead.
+fn classify_openai(err: &async_openai::error::OpenAIError) -> RetryClass {
+    match err {
+        async_openai::error::OpenAIError::Reqwest(e) => classify_reqwest(e),
+        async_openai::error::OpenAIError::ApiError(api_err) => {
+            if api_err.code.as_deref() == Some("rate_limit_exceeded") {
+                RetryClass::RateLimited
+            } else {
+                RetryClass::GiveUp
+            }
+        }
+        _ => RetryClass::GiveUp,
+    }
+}
+
+/// Retries `attempt` up to `config.max_attempts` times, giving up immediately on
+/// non-retryable errors (4xx other than 429), backing off `base_delay_ms * 10^n` ms
+/// on transient 5xx/network errors, and `100 + base_delay_ms * 10^n` ms on 429s.
+pub async fn with_retry<T, F, Fut>(config: RetryConfig, mut attempt: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut last_err = None;
+
+    for attempt_no in 1..=config.max_attempts {
+        match attempt().await {
+            Ok(value) => return Ok(value),
+            Err(err) => {
+                // Cap the exponent so `10u64.pow` can't overflow for a large
+                // RETRY_MAX_ATTEMPTS; the resulting delay is already far past any
+                // sane backoff ceiling well before this caps out.
+                let backoff = 10u64.saturating_pow(attempt_no.min(18));
+                let delay_ms = match classify(&err) {
+                    RetryClass::GiveUp => return Err(err),
+                    RetryClass::RetryLater => config.base_delay_ms.saturating_mul(backoff),
+                    RetryClass::RateLimited => 100u64.saturating_add(config.base_delay_ms.saturating_mul(backoff)),
+                };
+
+                if attempt_no < config.max_attempts {
+                    tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+                }
+
+                last_err = Some(err);
+            }
+        }
+    }
+
+    Err(last_err.expect("loop runs at least once, so an error was recorded"))
+}